@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
 use actix_web::{
@@ -11,16 +12,20 @@ use futures_util::future::{LocalBoxFuture, Ready, ready};
 use parking_lot::Mutex;
 use std::collections::VecDeque;
 
-// 请求统计数据结构
+// 请求统计数据结构。这里的计数字段都基于 Arc，
+// 因此克隆后仍然共享同一份计数状态，可以安全地在多个 worker 间共享同一个实例。
 #[derive(Debug, Clone)]
 pub struct RequestStats {
     per_minute: Arc<Mutex<SlidingWindowCounter>>,
     per_hour: Arc<Mutex<SlidingWindowCounter>>,
     per_day: Arc<Mutex<SlidingWindowCounter>>,
+    lifetime_total: Arc<AtomicU64>, // 持久化的生命周期请求总数
+    started_at: Instant,
 }
 
 impl RequestStats {
-    pub fn new() -> Self {
+    /// 创建统计实例，`initial_lifetime_total` 为从数据库恢复的历史总数
+    pub fn new(initial_lifetime_total: u64) -> Self {
         Self {
             per_minute: Arc::new(Mutex::new(SlidingWindowCounter::new(Duration::from_secs(
                 60,
@@ -31,6 +36,8 @@ impl RequestStats {
             per_day: Arc::new(Mutex::new(SlidingWindowCounter::new(Duration::from_secs(
                 86400,
             )))),
+            lifetime_total: Arc::new(AtomicU64::new(initial_lifetime_total)),
+            started_at: Instant::now(),
         }
     }
 
@@ -46,11 +53,20 @@ impl RequestStats {
         self.per_day.lock().count()
     }
 
+    pub fn lifetime_total(&self) -> u64 {
+        self.lifetime_total.load(Ordering::Relaxed)
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
     pub fn increment(&self) {
         let now = Instant::now();
         self.per_minute.lock().increment(now);
         self.per_hour.lock().increment(now);
         self.per_day.lock().increment(now);
+        self.lifetime_total.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -91,15 +107,210 @@ impl SlidingWindowCounter {
     }
 }
 
+// 请求耗时直方图的桶边界（秒），最后一个桶代表 +Inf
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, f64::INFINITY,
+];
+
+// 固定分桶的直方图，语义与 Prometheus histogram 一致
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    // 每个桶只记录落在该桶区间内的样本数，渲染时再做前缀和得到累计值
+    counts: Mutex<Vec<u64>>,
+    sum: Mutex<f64>,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: Mutex::new(vec![0; bounds.len()]),
+            sum: Mutex::new(0.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, value: f64) {
+        let idx = self
+            .bounds
+            .iter()
+            .position(|b| value <= *b)
+            .unwrap_or(self.bounds.len() - 1);
+        self.counts.lock()[idx] += 1;
+        *self.sum.lock() += value;
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 渲染为累计的 `_bucket`/`_sum`/`_count` 行
+    fn render(&self, name: &str, out: &mut String) {
+        use std::fmt::Write;
+
+        let _ = writeln!(out, "# HELP {name} Histogram of {name}.");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+
+        let counts = self.counts.lock();
+        let mut cumulative = 0u64;
+        for (bound, count) in self.bounds.iter().zip(counts.iter()) {
+            cumulative += count;
+            let le = if bound.is_infinite() {
+                "+Inf".to_string()
+            } else {
+                bound.to_string()
+            };
+            let _ = writeln!(out, "{name}_bucket{{le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_sum {}", *self.sum.lock());
+        let _ = writeln!(out, "{name}_count {}", self.count.load(Ordering::Relaxed));
+    }
+}
+
+// Prometheus / OpenMetrics 格式的服务端指标
+#[derive(Debug)]
+pub struct ServerMetrics {
+    total_requests: AtomicU64,
+    status_2xx: AtomicU64,
+    status_4xx: AtomicU64,
+    status_5xx: AtomicU64,
+    route_root: AtomicU64,
+    route_uuid: AtomicU64,
+    route_update_count: AtomicU64,
+    // 中间件在整个 HTTP 调用（路由 + handler + 序列化）外层计时，并非单条 SQL
+    // 查询的耗时，所以命名为请求耗时而不是 DB 查询耗时，避免误导排障
+    request_duration_seconds: Histogram,
+    // 真正围绕 `db.rs` 里实际的 sqlx 查询调用计时，与上面的整请求耗时是两条
+    // 独立的指标序列
+    db_query_duration_seconds: Histogram,
+}
+
+impl ServerMetrics {
+    pub fn new() -> Self {
+        Self {
+            total_requests: AtomicU64::new(0),
+            status_2xx: AtomicU64::new(0),
+            status_4xx: AtomicU64::new(0),
+            status_5xx: AtomicU64::new(0),
+            route_root: AtomicU64::new(0),
+            route_uuid: AtomicU64::new(0),
+            route_update_count: AtomicU64::new(0),
+            request_duration_seconds: Histogram::new(REQUEST_DURATION_BUCKETS),
+            db_query_duration_seconds: Histogram::new(REQUEST_DURATION_BUCKETS),
+        }
+    }
+
+    fn record_route(&self, route: &str) {
+        match route {
+            "/" => self.route_root.fetch_add(1, Ordering::Relaxed),
+            "/{uuid}" => self.route_uuid.fetch_add(1, Ordering::Relaxed),
+            "/update_count" => self.route_update_count.fetch_add(1, Ordering::Relaxed),
+            _ => 0,
+        };
+    }
+
+    fn record_status(&self, status: u16) {
+        match status / 100 {
+            2 => self.status_2xx.fetch_add(1, Ordering::Relaxed),
+            4 => self.status_4xx.fetch_add(1, Ordering::Relaxed),
+            5 => self.status_5xx.fetch_add(1, Ordering::Relaxed),
+            _ => 0,
+        };
+    }
+
+    fn record(&self, route: &str, status: u16) {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        self.record_route(route);
+        self.record_status(status);
+    }
+
+    /// 记录一次完整 HTTP 请求（路由 + handler + 序列化）的耗时
+    pub fn observe_request_duration(&self, elapsed: Duration) {
+        self.request_duration_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    /// 记录一次实际的 sqlx 查询调用耗时，由 `db.rs` 在发起查询处调用
+    pub fn observe_db_query_duration(&self, elapsed: Duration) {
+        self.db_query_duration_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP hitokoto_requests_total Total number of HTTP requests handled.\n");
+        out.push_str("# TYPE hitokoto_requests_total counter\n");
+        out.push_str(&format!(
+            "hitokoto_requests_total {}\n",
+            self.total_requests.load(Ordering::Relaxed)
+        ));
+
+        out.push_str(
+            "# HELP hitokoto_responses_total Total number of HTTP responses by status class.\n",
+        );
+        out.push_str("# TYPE hitokoto_responses_total counter\n");
+        out.push_str(&format!(
+            "hitokoto_responses_total{{status=\"2xx\"}} {}\n",
+            self.status_2xx.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "hitokoto_responses_total{{status=\"4xx\"}} {}\n",
+            self.status_4xx.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "hitokoto_responses_total{{status=\"5xx\"}} {}\n",
+            self.status_5xx.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# HELP hitokoto_route_requests_total Total number of requests per route.\n");
+        out.push_str("# TYPE hitokoto_route_requests_total counter\n");
+        out.push_str(&format!(
+            "hitokoto_route_requests_total{{route=\"/\"}} {}\n",
+            self.route_root.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "hitokoto_route_requests_total{{route=\"/{{uuid}}\"}} {}\n",
+            self.route_uuid.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "hitokoto_route_requests_total{{route=\"/update_count\"}} {}\n",
+            self.route_update_count.load(Ordering::Relaxed)
+        ));
+
+        self.request_duration_seconds
+            .render("hitokoto_request_duration_seconds", &mut out);
+        self.db_query_duration_seconds
+            .render("hitokoto_db_query_seconds", &mut out);
+
+        out
+    }
+}
+
+impl Default for ServerMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // 中间件
 #[derive(Clone)]
 pub struct RequestCounterMiddleware {
     stats: RequestStats,
+    metrics: Arc<ServerMetrics>,
+    log_requests: bool,
 }
 
 impl RequestCounterMiddleware {
-    pub fn new(stats: RequestStats) -> Self {
-        Self { stats }
+    pub fn new(stats: RequestStats, metrics: Arc<ServerMetrics>) -> Self {
+        Self {
+            stats,
+            metrics,
+            log_requests: false,
+        }
+    }
+
+    /// 开启逐请求的结构化访问日志（默认关闭，保持现有行为安静）
+    pub fn with_request_logging(mut self, enabled: bool) -> Self {
+        self.log_requests = enabled;
+        self
     }
 }
 
@@ -119,6 +330,8 @@ where
         ready(Ok(RequestCounterMiddlewareService {
             service,
             stats: self.stats.clone(),
+            metrics: self.metrics.clone(),
+            log_requests: self.log_requests,
         }))
     }
 }
@@ -126,6 +339,8 @@ where
 pub struct RequestCounterMiddlewareService<S> {
     service: S,
     stats: RequestStats,
+    metrics: Arc<ServerMetrics>,
+    log_requests: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for RequestCounterMiddlewareService<S>
@@ -144,10 +359,35 @@ where
         // 在请求处理前增加计数器
         self.stats.increment();
 
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| req.path().to_string());
+        let metrics = self.metrics.clone();
+        let log_requests = self.log_requests;
+        let start = Instant::now();
+
         let fut = self.service.call(req);
 
         Box::pin(async move {
             let res = fut.await?;
+            let elapsed = start.elapsed();
+
+            metrics.observe_request_duration(elapsed);
+            metrics.record(&route, res.status().as_u16());
+
+            if log_requests {
+                tracing::info!(
+                    method = %method,
+                    path = %path,
+                    route = %route,
+                    status = res.status().as_u16(),
+                    latency_ms = elapsed.as_secs_f64() * 1000.0,
+                    "request handled"
+                );
+            }
+
             Ok(res)
         })
     }
@@ -158,9 +398,18 @@ pub async fn get_stats(stats: web::Data<RequestStats>) -> impl Responder {
     HttpResponse::Ok()
         .content_type(ContentType::json())
         .body(format!(
-            r#"{{"requests_per_minute":{},"requests_per_hour":{},"requests_per_day":{}}}"#,
+            r#"{{"requests_per_minute":{},"requests_per_hour":{},"requests_per_day":{},"lifetime_total":{},"uptime_secs":{}}}"#,
             stats.requests_per_minute(),
             stats.requests_per_hour(),
-            stats.requests_per_day()
+            stats.requests_per_day(),
+            stats.lifetime_total(),
+            stats.uptime_secs()
         ))
 }
+
+// 用于获取 Prometheus/OpenMetrics 文本格式指标的 handler
+pub async fn get_metrics(metrics: web::Data<Arc<ServerMetrics>>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render())
+}
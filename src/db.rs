@@ -1,10 +1,14 @@
+use crate::counter::ServerMetrics;
 use crate::{Hitokoto, QueryParams};
 use arc_swap::ArcSwap;
 use rand::prelude::*;
-use sqlx::any::{AnyKind, AnyPool, AnyPoolOptions};
+use rand::seq::index;
+use sqlx::Connection;
+use sqlx::any::{AnyConnection, AnyKind, AnyPool, AnyPoolOptions};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex as AsyncMutex, mpsc};
 
 pub struct DbState {
     pub pool: AnyPool,               // 数据库连接池
@@ -12,6 +16,10 @@ pub struct DbState {
     pub max_length: AtomicI32,       // 最大长度
     pub min_length: AtomicI32,       // 最大长度
     pub uuids: ArcSwap<Vec<String>>, // UUID列表
+    pub metrics: Arc<ServerMetrics>, // 用于记录真实 sqlx 查询耗时的共享指标
+    /// `--memory` 模式下真正承担热路径读写的写者/读者分离连接池；磁盘模式下为
+    /// `None`，热路径查询继续走 `pool`（sqlx 自带的连接池调度）
+    pub query_pool: Option<Arc<WriterReaderPool>>,
 }
 
 impl Clone for DbState {
@@ -22,6 +30,8 @@ impl Clone for DbState {
             max_length: AtomicI32::new(self.max_length.load(Ordering::SeqCst)),
             min_length: AtomicI32::new(self.min_length.load(Ordering::SeqCst)),
             uuids: ArcSwap::new(self.uuids.load().clone()),
+            metrics: self.metrics.clone(),
+            query_pool: self.query_pool.clone(),
         }
     }
 }
@@ -56,18 +66,30 @@ impl DbState {
 /// - `max_connections`: 最大连接数
 /// - `connect_timeout`: 连接超时时间(秒)
 /// - `idle_timeout`: 空闲连接超时时间(秒)
+/// - `metrics`: 与 HTTP 层共用的指标实例，供后续查询记录 `hitokoto_db_query_seconds`
 pub async fn get_pool(
     database_url: &str,
     max_connections: u32,
     connect_timeout: u64,
     idle_timeout: u64,
+    metrics: Arc<ServerMetrics>,
 ) -> Result<DbState, sqlx::Error> {
-    let pool = AnyPoolOptions::new()
+    let mut options = AnyPoolOptions::new()
         .max_connections(max_connections)
         .acquire_timeout(Duration::from_secs(connect_timeout))
-        .idle_timeout(Duration::from_secs(idle_timeout))
-        .connect(database_url)
-        .await?;
+        .idle_timeout(Duration::from_secs(idle_timeout));
+
+    // 落盘 SQLite 也值得开 WAL：同一个 PRAGMA 调优（`PoolTuning` 的默认值）既用于
+    // `--memory` 的共享缓存内存库，也用在这里，避免只有内存模式才吃得到这些调优
+    if database_url.starts_with("sqlite:") {
+        let tuning = PoolTuning::default();
+        options = options.after_connect(move |conn, _meta| {
+            let tuning = tuning.clone();
+            Box::pin(async move { apply_sqlite_pragmas(conn, &tuning).await })
+        });
+    }
+
+    let pool = options.connect(database_url).await?;
 
     // 获取数据库类型
     let db_kind = pool.any_kind();
@@ -95,20 +117,247 @@ pub async fn get_pool(
         .await?;
     let uuids = Arc::new(uuids);
 
+    ensure_server_meta_table(&pool).await?;
+
+    if db_kind == AnyKind::Sqlite {
+        ensure_fts_table(&pool).await?;
+        populate_fts_if_empty(&pool).await?;
+    }
+
     Ok(DbState {
         pool,
         count,
         max_length: max_l,
         min_length: min_l,
         uuids: ArcSwap::new(uuids),
+        metrics,
+        query_pool: None,
     })
 }
 
+// 每行 7 列，140 行约为 980 个绑定参数，留有余量以不超过 SQLite 999 个绑定参数的上限
+const BULK_INSERT_CHUNK_SIZE: usize = 140;
+
+/// 将已有的 `Hitokoto`（保留 id）批量写入目标连接池：单个事务内按分片拼接
+/// 多行 `INSERT ... VALUES (...),(...)`，比逐行 `INSERT` 快得多
+async fn bulk_insert_with_id(pool: &AnyPool, records: &[Hitokoto]) -> Result<usize, sqlx::Error> {
+    let start = std::time::Instant::now();
+    let mut tx = pool.begin().await?;
+
+    for chunk in records.chunks(BULK_INSERT_CHUNK_SIZE) {
+        let placeholders = vec!["(?, ?, ?, ?, ?, ?, ?)"; chunk.len()].join(",");
+        let sql = format!(
+            "INSERT INTO hitokoto (id, uuid, text, type, from_source, from_who, length) VALUES {placeholders}"
+        );
+
+        let mut q = sqlx::query(&sql);
+        for h in chunk {
+            q = q
+                .bind(h.id)
+                .bind(&h.uuid)
+                .bind(&h.text)
+                .bind(&h.r#type)
+                .bind(&h.from_source)
+                .bind(&h.from_who)
+                .bind(h.length);
+        }
+        q.execute(&mut *tx).await?;
+    }
+
+    tx.commit().await?;
+
+    let elapsed = start.elapsed();
+    let rows_per_sec = records.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    tracing::info!(
+        rows = records.len(),
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        rows_per_sec,
+        "Bulk insert completed"
+    );
+
+    Ok(records.len())
+}
+
+/// SQLite 连接池的 PRAGMA 调优参数，由 [`WriterReaderPool`] 在连接建立时应用，
+/// `readers` 同时决定常驻读连接的数量。
+#[derive(Debug, Clone)]
+pub struct PoolTuning {
+    /// 常驻读连接的数量；读请求按轮询顺序分配到这些连接上，全部忙碌时转向
+    /// [`WriterReaderPool`] 的溢出路径，而不是排队等待
+    pub readers: u32,
+    pub busy_timeout_ms: u32,
+    /// 以 KiB 为单位的页缓存大小（正数），应用时取负写成 `PRAGMA cache_size=-N`
+    pub cache_size_kib: i64,
+    pub mmap_size_bytes: i64,
+}
+
+impl Default for PoolTuning {
+    fn default() -> Self {
+        Self {
+            readers: 8,
+            busy_timeout_ms: 5000,
+            cache_size_kib: 8192,
+            mmap_size_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+// 给一个刚建立的 SQLite 连接应用统一的 PRAGMA 调优，`get_pool`（落盘库）和
+// `load_data_to_memory`（内存库）共用同一套逻辑
+async fn apply_sqlite_pragmas(
+    conn: &mut sqlx::any::AnyConnection,
+    tuning: &PoolTuning,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("PRAGMA journal_mode=WAL").execute(&mut *conn).await?;
+    sqlx::query("PRAGMA synchronous=NORMAL").execute(&mut *conn).await?;
+    sqlx::query(&format!("PRAGMA busy_timeout={}", tuning.busy_timeout_ms))
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(&format!("PRAGMA cache_size=-{}", tuning.cache_size_kib))
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(&format!("PRAGMA mmap_size={}", tuning.mmap_size_bytes))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 一条专属写连接（置于 `Mutex` 之后）与若干条按轮询顺序分配的读连接，
+/// 全部读连接恰好忙碌时惰性开一条"溢出"连接应急，用完后放回 channel
+/// 构成的空闲列表供下次复用，而不是排队阻塞等待某条常驻读连接被归还。
+pub struct WriterReaderPool {
+    url: String,
+    tuning: PoolTuning,
+    writer: AsyncMutex<AnyConnection>,
+    readers: Vec<AsyncMutex<AnyConnection>>,
+    next_reader: AtomicUsize,
+    spill_tx: mpsc::UnboundedSender<AnyConnection>,
+    spill_rx: AsyncMutex<mpsc::UnboundedReceiver<AnyConnection>>,
+}
+
+/// 从 [`WriterReaderPool`] 借出的一条连接；`Deref`/`DerefMut` 到底层
+/// `AnyConnection`，供 `sqlx::query*` 的 `Executor` 调用直接使用
+pub enum PooledConn<'a> {
+    Writer(tokio::sync::MutexGuard<'a, AnyConnection>),
+    Reader(tokio::sync::MutexGuard<'a, AnyConnection>),
+    Spill {
+        conn: Option<AnyConnection>,
+        tx: mpsc::UnboundedSender<AnyConnection>,
+    },
+}
+
+impl std::ops::Deref for PooledConn<'_> {
+    type Target = AnyConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PooledConn::Writer(guard) | PooledConn::Reader(guard) => guard,
+            PooledConn::Spill { conn, .. } => conn.as_ref().expect("spill connection taken"),
+        }
+    }
+}
+
+impl std::ops::DerefMut for PooledConn<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            PooledConn::Writer(guard) | PooledConn::Reader(guard) => guard,
+            PooledConn::Spill { conn, .. } => conn.as_mut().expect("spill connection taken"),
+        }
+    }
+}
+
+impl Drop for PooledConn<'_> {
+    fn drop(&mut self) {
+        // 溢出连接用完后放回 channel 构成的空闲列表，下次溢出时优先复用，
+        // 而不是每次都重新开一条连接
+        if let PooledConn::Spill { conn, tx } = self {
+            if let Some(conn) = conn.take() {
+                let _ = tx.send(conn);
+            }
+        }
+    }
+}
+
+impl WriterReaderPool {
+    async fn connect_one(url: &str, tuning: &PoolTuning) -> Result<AnyConnection, sqlx::Error> {
+        let mut conn = AnyConnection::connect(url).await?;
+        apply_sqlite_pragmas(&mut conn, tuning).await?;
+        Ok(conn)
+    }
+
+    /// 建立一条写连接与 `tuning.readers` 条读连接，全部指向同一个共享缓存的
+    /// SQLite 数据库（如 `sqlite::memory:?cache=shared`），对每条连接应用
+    /// `tuning` 里的 PRAGMA
+    pub async fn connect(url: &str, tuning: PoolTuning) -> Result<Self, sqlx::Error> {
+        let writer = Self::connect_one(url, &tuning).await?;
+
+        let reader_count = tuning.readers.max(1) as usize;
+        let mut readers = Vec::with_capacity(reader_count);
+        for _ in 0..reader_count {
+            readers.push(AsyncMutex::new(Self::connect_one(url, &tuning).await?));
+        }
+
+        let (spill_tx, spill_rx) = mpsc::unbounded_channel();
+
+        Ok(Self {
+            url: url.to_string(),
+            tuning,
+            writer: AsyncMutex::new(writer),
+            readers,
+            next_reader: AtomicUsize::new(0),
+            spill_tx,
+            spill_rx: AsyncMutex::new(spill_rx),
+        })
+    }
+
+    /// 借出专属写连接
+    pub async fn writer(&self) -> PooledConn<'_> {
+        PooledConn::Writer(self.writer.lock().await)
+    }
+
+    /// 按轮询顺序借出一条读连接；只有当*所有*读连接都恰好被占用时才转向溢出
+    /// 路径：先试着复用 channel 里闲置的溢出连接，没有就惰性开一条。单个
+    /// 轮询到的连接忙碌不代表要溢出——还要看看其它读连接里有没有空闲的。
+    pub async fn reader(&self) -> Result<PooledConn<'_>, sqlx::Error> {
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        for offset in 0..self.readers.len() {
+            let idx = (start + offset) % self.readers.len();
+            if let Ok(guard) = self.readers[idx].try_lock() {
+                return Ok(PooledConn::Reader(guard));
+            }
+        }
+
+        let spare = self.spill_rx.lock().await.try_recv().ok();
+        let conn = match spare {
+            Some(conn) => conn,
+            None => Self::connect_one(&self.url, &self.tuning).await?,
+        };
+
+        Ok(PooledConn::Spill {
+            conn: Some(conn),
+            tx: self.spill_tx.clone(),
+        })
+    }
+}
+
 /// 将数据加载到内存中的SQLite数据库
-pub async fn load_data_to_memory(pool: &AnyPool) -> Result<DbState, sqlx::Error> {
-    // 创建内存中的SQLite数据库连接池
-    let memory_pool = AnyPoolOptions::new()
-        .max_connections(1) // 内存数据库通常只需要一个连接
+pub async fn load_data_to_memory(
+    pool: &AnyPool,
+    tuning: &PoolTuning,
+    metrics: Arc<ServerMetrics>,
+) -> Result<DbState, sqlx::Error> {
+    let tuning_for_connect = tuning.clone();
+
+    // 建表、灌数据、建索引这些一次性的准备工作仍然走一个普通的 sqlx 连接池
+    // （容量给得很小，够用即可）；真正承担热路径读写的是下面单独建的
+    // `WriterReaderPool`，两者指向同一个共享缓存数据库
+    // "sqlite::memory:?cache=shared"，靠 SQLite 的 shared-cache 机制共享数据。
+    let admin_pool = AnyPoolOptions::new()
+        .max_connections(2)
+        .after_connect(move |conn, _meta| {
+            let tuning = tuning_for_connect.clone();
+            Box::pin(async move { apply_sqlite_pragmas(conn, &tuning).await })
+        })
         .connect("sqlite::memory:?cache=shared")
         .await?;
 
@@ -126,7 +375,7 @@ pub async fn load_data_to_memory(pool: &AnyPool) -> Result<DbState, sqlx::Error>
         )
         "#,
     )
-    .execute(&memory_pool)
+    .execute(&admin_pool)
     .await?;
 
     // 从原始数据库复制数据
@@ -136,36 +385,20 @@ pub async fn load_data_to_memory(pool: &AnyPool) -> Result<DbState, sqlx::Error>
 
     let uuid_list: Vec<String> = hitokotos.iter().map(|h| h.uuid.clone()).collect(); // 创建UUID列表
 
-    for hitokoto in hitokotos {
-        sqlx::query(
-            r#"
-            INSERT INTO hitokoto (id, uuid, text, type, from_source, from_who, length)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(hitokoto.id)
-        .bind(hitokoto.uuid)
-        .bind(hitokoto.text)
-        .bind(hitokoto.r#type)
-        .bind(hitokoto.from_source)
-        .bind(hitokoto.from_who)
-        .bind(hitokoto.length)
-        .execute(&memory_pool)
-        .await?;
-    }
+    bulk_insert_with_id(&admin_pool, &hitokotos).await?;
 
     // 创建UUID索引
     sqlx::query("CREATE INDEX idx_uuid ON hitokoto (uuid)")
-        .execute(&memory_pool)
+        .execute(&admin_pool)
         .await?;
     // 创建类型,长度联合索引
     sqlx::query("CREATE INDEX idx_type_length ON hitokoto (type, length)")
-        .execute(&memory_pool)
+        .execute(&admin_pool)
         .await?;
 
     // 获取数据库统计信息
     let count: i32 = sqlx::query_scalar("SELECT COUNT(*) FROM hitokoto")
-        .fetch_one(&memory_pool)
+        .fetch_one(&admin_pool)
         .await?;
 
     let count: AtomicI32 = AtomicI32::new(count);
@@ -174,21 +407,35 @@ pub async fn load_data_to_memory(pool: &AnyPool) -> Result<DbState, sqlx::Error>
     let max_l = AtomicI32::new(max_l);
     let min_l = AtomicI32::new(min_l);
 
-    pool.close().await; // 关闭原始数据库连接
+    ensure_server_meta_table(&admin_pool).await?;
+
+    ensure_fts_table(&admin_pool).await?;
+    populate_fts_if_empty(&admin_pool).await?;
+
+    // 准备工作（建表/灌数据/建索引）全部完成后，再开写者/读者分离的查询池，
+    // 保证它的读连接一上来就能看到完整数据
+    let query_pool =
+        WriterReaderPool::connect("sqlite::memory:?cache=shared", tuning.clone()).await?;
+
+    // 不在这里关闭原始连接池：`server_meta`（如 lifetime_requests）仍然需要落在
+    // 真正持久化的后端上，调用方（见 main.rs）会继续用它读取/刷新这些状态，
+    // 而不是写进这个进程退出后就会消失的内存库。
     Ok(DbState {
-        pool: memory_pool,
+        pool: admin_pool,
         count,
         max_length: max_l,
         min_length: min_l,
         uuids: ArcSwap::new(Arc::new(uuid_list)),
+        metrics,
+        query_pool: Some(Arc::new(query_pool)),
     })
 }
 
-pub fn build_query_conditions(params: &QueryParams, state: &DbState) -> (String, Vec<String>) {
+// 构建 type/length 过滤条件的 WHERE 子句，供单条与批量查询共用
+fn build_where_clause(params: &QueryParams) -> (String, Vec<String>) {
     let mut conditions = Vec::new();
     let mut query_params: Vec<String> = Vec::new();
 
-    // 构建过滤条件（与之前相同）
     if let Some(categories) = &params.c {
         let categories: Vec<&str> = categories.split(',').collect();
         if !categories.is_empty() {
@@ -216,10 +463,19 @@ pub fn build_query_conditions(params: &QueryParams, state: &DbState) -> (String,
         "".to_string()
     };
 
-    let rand_function = match state.pool.any_kind() {
+    (where_clause, query_params)
+}
+
+fn rand_function(state: &DbState) -> &'static str {
+    match state.pool.any_kind() {
         AnyKind::MySql => "RAND()",
         _ => "RANDOM()",
-    };
+    }
+}
+
+pub fn build_query_conditions(params: &QueryParams, state: &DbState) -> (String, Vec<String>) {
+    let (where_clause, query_params) = build_where_clause(params);
+    let rand_function = rand_function(state);
 
     let sql = format!(
         "SELECT * FROM (
@@ -233,6 +489,101 @@ pub fn build_query_conditions(params: &QueryParams, state: &DbState) -> (String,
     (sql, query_params)
 }
 
+fn merge_condition(where_clause: String, condition: String) -> String {
+    if where_clause.is_empty() {
+        format!("WHERE {}", condition)
+    } else {
+        format!("{} AND {}", where_clause, condition)
+    }
+}
+
+/// `rand_hitokoto_batch` 支持的分页/排序选项
+#[derive(Debug, Default, Clone)]
+pub struct BatchOptions {
+    pub limit: i64,
+    pub offset: i64,
+    pub reverse: bool,
+    pub after_id: Option<i32>,
+    pub before_id: Option<i32>,
+}
+
+impl BatchOptions {
+    // 只有显式要求确定性排序时才放弃随机抽样
+    fn wants_deterministic_order(&self) -> bool {
+        self.reverse || self.after_id.is_some() || self.before_id.is_some()
+    }
+}
+
+/// 批量获取不重复的随机记录。无过滤条件且不分页时，直接在已缓存的 uuid 列表
+/// 中做不放回抽样，避免对数据库做昂贵的随机排序；否则回退为 SQL 查询。
+pub async fn rand_hitokoto_batch(
+    state: &DbState,
+    filter: &QueryParams,
+    opts: &BatchOptions,
+) -> Result<Vec<Hitokoto>, sqlx::Error> {
+    let filter_is_empty =
+        filter.c.is_none() && filter.min_length.is_none() && filter.max_length.is_none();
+
+    if filter_is_empty && opts.offset == 0 && !opts.wants_deterministic_order() {
+        let uuids = state.uuids.load();
+        let n = (opts.limit.max(0) as usize).min(uuids.len());
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+
+        let sample = index::sample(&mut rand::rng(), uuids.len(), n);
+        let mut results = Vec::with_capacity(n);
+        for idx in sample.iter() {
+            if let Some(h) =
+                execute_query_with_params(state, "SELECT * FROM hitokoto WHERE uuid = ?", &[
+                    uuids[idx].as_str(),
+                ])
+                .await?
+            {
+                results.push(h);
+            }
+        }
+        return Ok(results);
+    }
+
+    let (mut where_clause, mut query_params) = build_where_clause(filter);
+
+    if let Some(after) = opts.after_id {
+        where_clause = merge_condition(where_clause, "id > ?".to_string());
+        query_params.push(after.to_string());
+    }
+    if let Some(before) = opts.before_id {
+        where_clause = merge_condition(where_clause, "id < ?".to_string());
+        query_params.push(before.to_string());
+    }
+
+    let order = if opts.wants_deterministic_order() {
+        if opts.reverse { "id DESC" } else { "id ASC" }
+    } else {
+        rand_function(state)
+    };
+
+    let sql =
+        format!("SELECT * FROM hitokoto {where_clause} ORDER BY {order} LIMIT ? OFFSET ?");
+
+    let mut q = sqlx::query_as::<_, Hitokoto>(&sql);
+    for p in &query_params {
+        q = q.bind(p);
+    }
+    q = q.bind(opts.limit).bind(opts.offset);
+
+    let start = Instant::now();
+    let result = match &state.query_pool {
+        Some(query_pool) => {
+            let mut conn = query_pool.reader().await?;
+            q.fetch_all(&mut *conn).await
+        }
+        None => q.fetch_all(&state.pool).await,
+    };
+    state.metrics.observe_db_query_duration(start.elapsed());
+    result
+}
+
 // 通用查询执行函数
 pub async fn execute_query_with_params(
     state: &DbState,
@@ -243,7 +594,16 @@ pub async fn execute_query_with_params(
     for param in params {
         q = q.bind(param);
     }
-    q.fetch_optional(&state.pool).await
+    let start = Instant::now();
+    let result = match &state.query_pool {
+        Some(query_pool) => {
+            let mut conn = query_pool.reader().await?;
+            q.fetch_optional(&mut *conn).await
+        }
+        None => q.fetch_optional(&state.pool).await,
+    };
+    state.metrics.observe_db_query_duration(start.elapsed());
+    result
 }
 
 pub async fn rand_hitokoto_without_params(
@@ -294,6 +654,220 @@ pub async fn table_exists(pool: &AnyPool, table_name: &str) -> Result<bool, sqlx
     Ok(exists)
 }
 
+/// 确保 `server_meta` 键值表存在，用于持久化跨重启保留的服务端状态（如生命周期请求总数）
+pub async fn ensure_server_meta_table(pool: &AnyPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS server_meta (
+            meta_key VARCHAR(64) PRIMARY KEY,
+            meta_value TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// 读取 `server_meta` 中的一个整数值，不存在时返回 0
+pub async fn get_meta_u64(pool: &AnyPool, key: &str) -> Result<u64, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT meta_value FROM server_meta WHERE meta_key = ?")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(row.and_then(|(v,)| v.parse().ok()).unwrap_or(0))
+}
+
+/// 写入/更新 `server_meta` 中的一个整数值
+pub async fn set_meta_u64(pool: &AnyPool, key: &str, value: u64) -> Result<(), sqlx::Error> {
+    let query = match pool.any_kind() {
+        AnyKind::MySql => {
+            "INSERT INTO server_meta (meta_key, meta_value) VALUES (?, ?)
+             ON DUPLICATE KEY UPDATE meta_value = VALUES(meta_value)"
+        }
+        _ => {
+            "INSERT INTO server_meta (meta_key, meta_value) VALUES (?, ?)
+             ON CONFLICT(meta_key) DO UPDATE SET meta_value = excluded.meta_value"
+        }
+    };
+
+    sqlx::query(query)
+        .bind(key)
+        .bind(value.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 创建与 hitokoto 内容关联的 FTS5 虚拟表（仅 SQLite 支持），并挂上同步触发器，
+// 使得 chunk0-5 的 `--update` upsert 路径和 chunk1-3 的 JSON 导入在写入 `hitokoto`
+// 之后，`hitokoto_fts` 能继续保持同步，而不需要每次都手动重建索引
+pub(crate) async fn ensure_fts_table(pool: &AnyPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS hitokoto_fts USING fts5(
+            text, from_source, from_who, content='hitokoto', content_rowid='id'
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS hitokoto_fts_ai AFTER INSERT ON hitokoto BEGIN
+            INSERT INTO hitokoto_fts(rowid, text, from_source, from_who)
+            VALUES (new.id, new.text, new.from_source, new.from_who);
+        END",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS hitokoto_fts_ad AFTER DELETE ON hitokoto BEGIN
+            INSERT INTO hitokoto_fts(hitokoto_fts, rowid, text, from_source, from_who)
+            VALUES ('delete', old.id, old.text, old.from_source, old.from_who);
+        END",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS hitokoto_fts_au AFTER UPDATE ON hitokoto BEGIN
+            INSERT INTO hitokoto_fts(hitokoto_fts, rowid, text, from_source, from_who)
+            VALUES ('delete', old.id, old.text, old.from_source, old.from_who);
+            INSERT INTO hitokoto_fts(rowid, text, from_source, from_who)
+            VALUES (new.id, new.text, new.from_source, new.from_who);
+        END",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 首次创建时把已有数据灌入 FTS 索引
+pub(crate) async fn populate_fts_if_empty(pool: &AnyPool) -> Result<(), sqlx::Error> {
+    // 外部内容表上的 COUNT(*) 会读回 `hitokoto` 本身的行数，所以改查 FTS5 的
+    // `_docsize` 影子表，它只记录真正进入索引的文档
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM hitokoto_fts_docsize")
+        .fetch_one(pool)
+        .await?;
+
+    if count == 0 {
+        sqlx::query(
+            "INSERT INTO hitokoto_fts(rowid, text, from_source, from_who)
+             SELECT id, text, from_source, from_who FROM hitokoto",
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// 关键词搜索使用的匹配模式
+pub enum SearchMode {
+    /// `"term"*` —— 前缀匹配
+    Prefix,
+    /// 按空白切分后逐词 AND 前缀匹配
+    Fuzzy,
+    /// 整体作为短语匹配
+    Phrase,
+}
+
+impl SearchMode {
+    pub fn parse(mode: Option<&str>) -> Self {
+        match mode {
+            Some("fuzzy") => SearchMode::Fuzzy,
+            Some("phrase") => SearchMode::Phrase,
+            _ => SearchMode::Prefix,
+        }
+    }
+}
+
+// 转义 FTS5 查询字符串中的双引号，避免破坏 MATCH 语法
+fn escape_fts_term(term: &str) -> String {
+    term.replace('"', "\"\"")
+}
+
+fn build_fts_match(keyword: &str, mode: &SearchMode) -> String {
+    match mode {
+        SearchMode::Prefix => format!("\"{}\"*", escape_fts_term(keyword.trim())),
+        SearchMode::Phrase => format!("\"{}\"", escape_fts_term(keyword.trim())),
+        SearchMode::Fuzzy => keyword
+            .split_whitespace()
+            .map(|term| format!("\"{}\"*", escape_fts_term(term)))
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    }
+}
+
+const SEARCH_RESULT_LIMIT: i64 = 50;
+
+/// 按关键词搜索语句正文/出处/作者。SQLite 使用 FTS5，其它方言退化为 LIKE 匹配。
+pub async fn search_hitokoto(
+    state: &DbState,
+    params: &QueryParams,
+) -> Result<Vec<Hitokoto>, sqlx::Error> {
+    let keyword = match params.keyword.as_deref().map(str::trim) {
+        Some(k) if !k.is_empty() => k,
+        _ => return Ok(Vec::new()),
+    };
+
+    let start = Instant::now();
+    let result = match state.pool.any_kind() {
+        AnyKind::Sqlite => {
+            let mode = SearchMode::parse(params.search_mode.as_deref());
+            let match_expr = build_fts_match(keyword, &mode);
+
+            let q = sqlx::query_as::<_, Hitokoto>(
+                "SELECT hitokoto.* FROM hitokoto_fts
+                 JOIN hitokoto ON hitokoto.id = hitokoto_fts.rowid
+                 WHERE hitokoto_fts MATCH ?
+                 ORDER BY bm25(hitokoto_fts)
+                 LIMIT ?",
+            )
+            .bind(match_expr)
+            .bind(SEARCH_RESULT_LIMIT);
+
+            match &state.query_pool {
+                Some(query_pool) => {
+                    let mut conn = query_pool.reader().await?;
+                    q.fetch_all(&mut *conn).await
+                }
+                None => q.fetch_all(&state.pool).await,
+            }
+        }
+        _ => {
+            // FTS5 只在 SQLite 上可用，MySQL/PostgreSQL 退化为 LIKE 匹配
+            let like = format!(
+                "%{}%",
+                keyword.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+            );
+
+            let q = sqlx::query_as::<_, Hitokoto>(
+                "SELECT * FROM hitokoto
+                 WHERE text LIKE ? OR from_source LIKE ? OR from_who LIKE ?
+                 LIMIT ?",
+            )
+            .bind(&like)
+            .bind(&like)
+            .bind(&like)
+            .bind(SEARCH_RESULT_LIMIT);
+
+            match &state.query_pool {
+                Some(query_pool) => {
+                    let mut conn = query_pool.reader().await?;
+                    q.fetch_all(&mut *conn).await
+                }
+                None => q.fetch_all(&state.pool).await,
+            }
+        }
+    };
+    state.metrics.observe_db_query_duration(start.elapsed());
+    result
+}
+
 pub async fn get_length_stats(pool: &AnyPool) -> Result<(i32, i32), sqlx::Error> {
     let (max, min): (i32, i32) = sqlx::query_as(
         r#"
@@ -50,7 +50,7 @@ pub async fn init_db(db_url: &str) -> Result<(), Error> {
     let mut total_inserted = 0;
 
     for category in version_data.sentences {
-        println!("\nProcessing category: {}", category.name);
+        tracing::info!(category = %category.name, "Processing category");
 
         let sentences =
             fetch_category_data(&category.key, &category.name, category.timestamp).await?;
@@ -64,10 +64,7 @@ pub async fn init_db(db_url: &str) -> Result<(), Error> {
     // 创建索引
     create_indexes(&pool).await.unwrap();
     pool.close().await;
-    println!(
-        "\nOperation completed, a total of {} records were processed",
-        total_inserted
-    );
+    tracing::info!(total_inserted, "Operation completed");
 
     Ok(())
 }
@@ -85,7 +82,7 @@ async fn fetch_category_data(
 
         // 检查缓存是否需要更新
         if timestamp <= cached_data.timestamp {
-            println!("缓存的 {} 数据是最新的，无需更新", name);
+            tracing::info!(category = %name, "Cached data is up to date, skipping download");
             return Ok(cached_data.sentences);
         }
     }
@@ -98,7 +95,7 @@ async fn fetch_category_data(
     let response = client.get(&url).send().await.unwrap();
 
     let sentences: Vec<Sentence> = response.json().await.unwrap();
-    println!("成功下载 {} 数据", name);
+    tracing::info!(category = %name, "Downloaded category data");
 
     // 保存到缓存
     let cache_data = CategoryData {
@@ -113,35 +110,197 @@ async fn fetch_category_data(
     Ok(sentences)
 }
 
+// 每行 6 列，140 行约为 840 个绑定参数，与 hitokoto 表的分片大小保持一致
+const BULK_INSERT_CHUNK_SIZE: usize = 140;
+
+// 单个事务内按分片拼接多行 INSERT，而不是逐行单独 INSERT
 async fn batch_insert_sentences(
     pool: &AnyPool,
     sentences: &[Sentence],
 ) -> Result<usize, sqlx::Error> {
+    let start = std::time::Instant::now();
+    let mut tx = pool.begin().await?;
+
+    for chunk in sentences.chunks(BULK_INSERT_CHUNK_SIZE) {
+        let placeholders = vec!["(?, ?, ?, ?, ?, ?)"; chunk.len()].join(",");
+        let sql = format!(
+            "INSERT INTO hitokoto (uuid, text, type, from_source, from_who, length) VALUES {placeholders}"
+        );
+
+        let mut q = sqlx::query(&sql);
+        for sentence in chunk {
+            q = q
+                .bind(&sentence.uuid)
+                .bind(&sentence.hitokoto)
+                .bind(&sentence.sentence_type)
+                .bind(&sentence.from)
+                .bind(&sentence.from_who)
+                .bind(sentence.length);
+        }
+        q.execute(&mut *tx).await?;
+    }
+
+    tx.commit().await?;
+
+    let elapsed = start.elapsed();
+    let rows_per_sec = sentences.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    tracing::info!(
+        count = sentences.len(),
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        rows_per_sec,
+        "Inserted records"
+    );
+
+    Ok(sentences.len())
+}
+
+/// 确保 `category_version` 元数据表存在，记录每个分类最近一次同步的 `version.json` 时间戳
+async fn ensure_category_version_table(pool: &AnyPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS category_version (
+            category_key VARCHAR(64) PRIMARY KEY,
+            timestamp BIGINT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_category_version(pool: &AnyPool, key: &str) -> Result<u64, sqlx::Error> {
+    let row: Option<(i64,)> =
+        sqlx::query_as("SELECT timestamp FROM category_version WHERE category_key = ?")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(row.map(|(ts,)| ts as u64).unwrap_or(0))
+}
+
+async fn set_category_version(
+    pool: &AnyPool,
+    key: &str,
+    timestamp: u64,
+) -> Result<(), sqlx::Error> {
+    let query = match pool.any_kind() {
+        sqlx::any::AnyKind::MySql => {
+            "INSERT INTO category_version (category_key, timestamp) VALUES (?, ?)
+             ON DUPLICATE KEY UPDATE timestamp = VALUES(timestamp)"
+        }
+        _ => {
+            "INSERT INTO category_version (category_key, timestamp) VALUES (?, ?)
+             ON CONFLICT(category_key) DO UPDATE SET timestamp = excluded.timestamp"
+        }
+    };
+
+    sqlx::query(query)
+        .bind(key)
+        .bind(timestamp as i64)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 按 uuid 进行 upsert，保留已有记录的 id，而不是清空重建
+async fn upsert_sentences(pool: &AnyPool, sentences: &[Sentence]) -> Result<usize, sqlx::Error> {
+    let upsert_suffix = match pool.any_kind() {
+        sqlx::any::AnyKind::MySql => {
+            "ON DUPLICATE KEY UPDATE text = VALUES(text), type = VALUES(type),
+             from_source = VALUES(from_source), from_who = VALUES(from_who), length = VALUES(length)"
+        }
+        _ => {
+            "ON CONFLICT(uuid) DO UPDATE SET text = excluded.text, type = excluded.type,
+             from_source = excluded.from_source, from_who = excluded.from_who, length = excluded.length"
+        }
+    };
+
     let mut tx = pool.begin().await?;
 
     for sentence in sentences {
-        sqlx::query(
+        let query = format!(
             r#"
             INSERT INTO hitokoto (uuid, text, type, from_source, from_who, length)
             VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&sentence.uuid)
-        .bind(&sentence.hitokoto)
-        .bind(&sentence.sentence_type)
-        .bind(&sentence.from)
-        .bind(&sentence.from_who)
-        .bind(sentence.length)
-        .execute(&mut *tx)
-        .await?;
+            {upsert_suffix}
+            "#
+        );
+
+        sqlx::query(&query)
+            .bind(&sentence.uuid)
+            .bind(&sentence.hitokoto)
+            .bind(&sentence.sentence_type)
+            .bind(&sentence.from)
+            .bind(&sentence.from_who)
+            .bind(sentence.length)
+            .execute(&mut *tx)
+            .await?;
     }
 
     tx.commit().await?;
-    println!("成功插入 {} 条记录", sentences.len());
 
     Ok(sentences.len())
 }
 
+/// 从一份官方 sentence bundle 的 JSON 文件（`{uuid,hitokoto,type,from,from_who,length}` 数组）
+/// 批量导入数据，需要时自动建表，供运营者在没有预置 SQLite 文件的情况下从零播种数据库
+pub async fn import_from_json(path: &str, target_url: &str) -> Result<(), Error> {
+    let pool = get_pool_for_update(target_url).await.unwrap();
+
+    let content = fs::read_to_string(path)?;
+    let sentences: Vec<Sentence> = serde_json::from_str(&content)?;
+
+    let inserted = batch_insert_sentences(&pool, &sentences).await.unwrap();
+
+    create_indexes(&pool).await.unwrap();
+    pool.close().await;
+
+    tracing::info!(path, inserted, "Imported sentences from JSON file");
+
+    Ok(())
+}
+
+/// 增量更新：只拉取 `version.json` 中时间戳有变化的分类，按 uuid upsert，
+/// 已有记录的 id/uuid 保持不变，服务可以在不停机的情况下重新加载数据
+pub async fn update_db(db_url: &str) -> Result<(), Error> {
+    let pool = get_pool_for_update(db_url).await.unwrap();
+
+    fs::create_dir_all(CACHE_DIR)?;
+
+    let version_data = get_version().await.unwrap();
+
+    let mut total_updated = 0;
+
+    for category in version_data.sentences {
+        let stored_version = get_category_version(&pool, &category.key).await.unwrap();
+
+        if category.timestamp <= stored_version {
+            tracing::info!(category = %category.name, "No changes, skipping");
+            continue;
+        }
+
+        tracing::info!(category = %category.name, "Change detected, processing category");
+
+        let sentences =
+            fetch_category_data(&category.key, &category.name, category.timestamp).await?;
+
+        let updated = upsert_sentences(&pool, &sentences).await.unwrap();
+        set_category_version(&pool, &category.key, category.timestamp)
+            .await
+            .unwrap();
+
+        total_updated += updated;
+    }
+
+    create_indexes(&pool).await.unwrap();
+    pool.close().await;
+
+    tracing::info!(total_updated, "Incremental update completed");
+
+    Ok(())
+}
+
 async fn create_indexes(pool: &AnyPool) -> Result<(), sqlx::Error> {
     let mut conn = pool.acquire().await?;
 
@@ -165,29 +324,8 @@ async fn get_version() -> Result<VersionData, reqwest::Error> {
     Ok(version_data)
 }
 
-async fn get_pool(db_url: &str) -> Result<AnyPool, sqlx::Error> {
-    // 检查是否是 SQLite 数据库连接
-    if db_url.starts_with("sqlite:") {
-        // 检查数据库是否存在，不存在则创建
-        if Any::database_exists(db_url).await? {
-            Any::drop_database(db_url).await?;
-        }
-        Any::create_database(db_url).await?;
-    }
-
-    // 创建数据库连接池
-    let pool = AnyPoolOptions::new()
-        .max_connections(1)
-        .connect(db_url)
-        .await?;
-
-    if table_exists(&pool, "hitokoto").await? {
-        sqlx::query(&format!("DROP TABLE {}", "hitokoto"))
-            .execute(&pool)
-            .await?;
-    }
-
-    let create_table_sql = match pool.any_kind() {
+fn create_table_sql(kind: sqlx::any::AnyKind) -> String {
+    match kind {
         sqlx::any::AnyKind::Sqlite => r#"
                 CREATE TABLE IF NOT EXISTS hitokoto (
                     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -212,10 +350,72 @@ async fn get_pool(db_url: &str) -> Result<AnyPool, sqlx::Error> {
                 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4
                 "#
         .to_string(),
-        _ => unreachable!(),
-    };
+        sqlx::any::AnyKind::Postgres => r#"
+                CREATE TABLE IF NOT EXISTS hitokoto (
+                    id SERIAL PRIMARY KEY,
+                    uuid VARCHAR(36) UNIQUE NOT NULL,
+                    text TEXT NOT NULL,
+                    type VARCHAR(1) NOT NULL,
+                    from_source TEXT NOT NULL,
+                    from_who TEXT,
+                    length INT NOT NULL
+                )
+                "#
+        .to_string(),
+        kind => panic!("Unsupported database kind: {:?}", kind),
+    }
+}
 
-    sqlx::query(&create_table_sql).execute(&pool).await?;
+async fn get_pool(db_url: &str) -> Result<AnyPool, sqlx::Error> {
+    // 检查是否是 SQLite 数据库连接
+    if db_url.starts_with("sqlite:") {
+        // 检查数据库是否存在，不存在则创建
+        if Any::database_exists(db_url).await? {
+            Any::drop_database(db_url).await?;
+        }
+        Any::create_database(db_url).await?;
+    }
+
+    // 创建数据库连接池
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect(db_url)
+        .await?;
+
+    if table_exists(&pool, "hitokoto").await? {
+        sqlx::query(&format!("DROP TABLE {}", "hitokoto"))
+            .execute(&pool)
+            .await?;
+    }
+
+    sqlx::query(&create_table_sql(pool.any_kind()))
+        .execute(&pool)
+        .await?;
+
+    Ok(pool)
+}
+
+/// 打开一个用于增量更新的连接池：不会清空已有数据，只在表不存在时创建
+async fn get_pool_for_update(db_url: &str) -> Result<AnyPool, sqlx::Error> {
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect(db_url)
+        .await?;
+
+    sqlx::query(&create_table_sql(pool.any_kind()))
+        .execute(&pool)
+        .await?;
+
+    crate::db::ensure_server_meta_table(&pool).await?;
+    ensure_category_version_table(&pool).await?;
+
+    // 确保 FTS5 索引（及其同步触发器）在写入前已就绪，这样 `--update`/
+    // `--import-json` 新增或修改的行能被触发器实时同步进 `hitokoto_fts`，
+    // 而不是让 `/search` 一直停留在上一次服务启动时的快照
+    if pool.any_kind() == sqlx::any::AnyKind::Sqlite {
+        crate::db::ensure_fts_table(&pool).await?;
+        crate::db::populate_fts_if_empty(&pool).await?;
+    }
 
     Ok(pool)
 }
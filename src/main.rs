@@ -1,30 +1,36 @@
 // main.rs
 use actix_web::{
-    App, Either, HttpResponse, HttpServer, Responder, get, http::header::ContentType, web,
+    App, HttpRequest, HttpResponse, HttpServer, Responder, get,
+    http::header::{ACCEPT, ContentType},
+    web,
 };
 use clap::Parser;
+use serde::Serialize;
 use sqlx::FromRow;
 use std::env;
-use std::sync::atomic::Ordering;
 
 mod counter;
 mod db;
 mod init;
+mod store;
 use db::*;
 
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::middleware::Compress;
+use tracing_subscriber::EnvFilter;
 
 #[cfg(all(feature = "mimalloc", not(target_env = "msvc")))]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-#[derive(FromRow)]
+#[derive(FromRow, Serialize, Clone)]
 struct Hitokoto {
     id: i32,
     uuid: String,
     text: String,
+    #[serde(rename = "type")]
     r#type: String,
+    #[serde(rename = "from")]
     from_source: String,
     from_who: Option<String>,
     length: i32,
@@ -32,31 +38,45 @@ struct Hitokoto {
 
 impl Hitokoto {
     pub fn to_json(&self) -> String {
-        let from_who = match &self.from_who {
-            Some(who) => format!("\"{}\"", who),
-            None => "null".to_string(),
-        };
-
-        format!(
-            r#"{{"id":{},"uuid":"{}","text":"{}","type":"{}","from":"{}","from_who":{},"length":{}}}"#,
-            self.id,
-            self.uuid,
-            self.text.replace('"', "\\\""), // 转义双引号
-            self.r#type,
-            self.from_source.replace('"', "\\\""),
-            from_who,
-            self.length
-        )
+        serde_json::to_string(self).expect("Hitokoto serialization cannot fail")
     }
 }
 
 // 查询参数结构
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, Default)]
 struct QueryParams {
     c: Option<String>,
     encode: Option<String>,
     min_length: Option<i32>,
     max_length: Option<i32>,
+    // JSONP 模式下用于包裹 JSON 的回调函数名
+    callback: Option<String>,
+    // 关键词搜索，参见 `db::search_hitokoto`
+    keyword: Option<String>,
+    // 搜索模式：prefix（默认）/fuzzy/phrase
+    search_mode: Option<String>,
+}
+
+// `/batch` 允许在 QueryParams 的基础上指定一次取多少条
+const BATCH_MAX_N: i32 = 30;
+
+#[derive(serde::Deserialize)]
+struct BatchQueryParams {
+    c: Option<String>,
+    encode: Option<String>,
+    min_length: Option<i32>,
+    max_length: Option<i32>,
+    #[serde(default = "default_batch_n")]
+    n: i32,
+    // 分页/排序选项：都不设置时按随机顺序返回（无过滤条件时走 uuid 抽样的快速路径）
+    offset: Option<i64>,
+    reverse: Option<bool>,
+    after_id: Option<i32>,
+    before_id: Option<i32>,
+}
+
+fn default_batch_n() -> i32 {
+    1
 }
 
 #[derive(Parser)]
@@ -141,6 +161,103 @@ struct Cli {
     #[cfg(feature = "init")]
     #[arg(long, help = "Initialize database")]
     init: bool,
+
+    /// Incrementally re-sync from the sentences-bundle without dropping the table
+    #[cfg(feature = "init")]
+    #[arg(long, help = "Incrementally update the database from sentences-bundle")]
+    update: bool,
+
+    /// Seed the database from a local sentences-bundle JSON file
+    #[cfg(feature = "init")]
+    #[arg(long, value_name = "PATH", help = "Import sentences from a JSON file")]
+    import_json: Option<String>,
+
+    /// Log verbosity, passed to a tracing_subscriber EnvFilter (e.g. "info", "debug,sqlx=warn")
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        default_value = "info",
+        help = "Sets the log verbosity",
+        env = "HITOKOTO_LOG"
+    )]
+    log_level: String,
+
+    /// Emit one structured access-log event per request. Off by default to keep things quiet.
+    #[arg(
+        long,
+        help = "Log method/path/status/latency for every request",
+        env = "HITOKOTO_REQUEST_LOG"
+    )]
+    request_log: bool,
+
+    /// Number of concurrent reader connections for `--memory` mode (WAL allows many readers)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 8,
+        help = "Reader connections to open against the in-memory SQLite pool",
+        env = "HITOKOTO_MEMORY_READERS"
+    )]
+    memory_readers: u32,
+
+    /// `PRAGMA busy_timeout` (ms) applied to every in-memory pool connection
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 5000,
+        help = "Busy timeout (ms) for the in-memory SQLite pool",
+        env = "HITOKOTO_MEMORY_BUSY_TIMEOUT_MS"
+    )]
+    memory_busy_timeout_ms: u32,
+
+    /// `PRAGMA cache_size` (in KiB) applied to every in-memory pool connection
+    #[arg(
+        long,
+        value_name = "KIB",
+        default_value_t = 8192,
+        help = "Page cache size (KiB) for the in-memory SQLite pool",
+        env = "HITOKOTO_MEMORY_CACHE_KIB"
+    )]
+    memory_cache_kib: i64,
+
+    /// `PRAGMA mmap_size` (bytes) applied to every in-memory pool connection
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 256 * 1024 * 1024,
+        help = "mmap size (bytes) for the in-memory SQLite pool",
+        env = "HITOKOTO_MEMORY_MMAP_BYTES"
+    )]
+    memory_mmap_bytes: i64,
+
+    /// Which `HitokotoStore` backend serves requests: `sql` queries the configured
+    /// database pool (or its `--memory` SQLite copy) on every request; `memory` loads
+    /// all records once into a plain `Vec`/`HashMap` and serves `/`, `/{uuid}` and
+    /// `/batch` from it without SQL (`/search` still queries the database pool),
+    /// which fits small deployments where the whole dataset comfortably fits in RAM
+    #[arg(
+        long,
+        value_name = "BACKEND",
+        default_value_t = StoreBackend::Sql,
+        help = "Store backend to serve requests from",
+        env = "HITOKOTO_STORE_BACKEND"
+    )]
+    store_backend: StoreBackend,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum StoreBackend {
+    Sql,
+    Memory,
+}
+
+impl std::fmt::Display for StoreBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreBackend::Sql => write!(f, "sql"),
+            StoreBackend::Memory => write!(f, "memory"),
+        }
+    }
 }
 
 #[actix_web::main]
@@ -155,43 +272,114 @@ async fn main() -> std::io::Result<()> {
     let memory = cli.memory;
     let use_limiter = cli.limiter;
     let limiter_rate = cli.limiter_rate;
+    let request_log = cli.request_log;
+    let store_backend = cli.store_backend;
 
-    let bind_addr = format!("{}:{}", host, port);
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&cli.log_level))
+        .init();
 
-    println!("Welcome to hitokoto-rust!");
-    println!("Version: {}", env!("CARGO_PKG_VERSION"));
+    let bind_addr = format!("{}:{}", host, port);
 
-    if database_url.starts_with("postgres") {
-        println!("Don't use PostgreSQL with hitokoto-rust!");
-        return Ok(());
-    }
+    tracing::info!("Welcome to hitokoto-rust!");
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "Starting up");
 
     #[cfg(feature = "init")]
     if cli.init {
-        println!("Initializing database...");
+        tracing::info!("Initializing database...");
         init::init_db(&database_url).await.unwrap();
-        println!("Database initialized.");
+        tracing::info!("Database initialized.");
     }
 
+    #[cfg(feature = "init")]
+    if cli.update {
+        tracing::info!("Incrementally updating database...");
+        init::update_db(&database_url).await.unwrap();
+        tracing::info!("Database update complete.");
+    }
+
+    #[cfg(feature = "init")]
+    if let Some(path) = &cli.import_json {
+        tracing::info!(path, "Importing sentences from JSON file...");
+        init::import_from_json(path, &database_url).await.unwrap();
+        tracing::info!("Import complete.");
+    }
+
+    // 指标在所有 worker 之间共享一份，而非每个 worker 各自独立统计——否则
+    // `/metrics` 的返回值只取决于碰巧接到这次请求的那个 worker。DB 连接池
+    // 建立得更早，所以这里提前构造，供 `get_pool`/`load_data_to_memory` 记录
+    // 真实的 sqlx 查询耗时（`hitokoto_db_query_seconds`）。
+    let server_metrics = std::sync::Arc::new(counter::ServerMetrics::new());
+
     // Initialize database connection pool with max connections
-    let pool: DbState = get_pool(&database_url, max_connections, 10, 60)
+    let pool: DbState = get_pool(&database_url, max_connections, 10, 60, server_metrics.clone())
         .await
         .unwrap();
 
+    // `server_meta`（生命周期请求总数等）始终落在这个原始后端上，哪怕 `--memory`
+    // 把热路径切到了内存 SQLite 池：内存池在进程退出时就没了，写进那里的统计
+    // 没法跨重启保留，所以这里单独留一份原始连接池的句柄只用来读/写元数据。
+    let meta_pool = pool.pool.clone();
+
     let pool = if memory {
-        println!("Loading data into memory SQLite database...");
-        load_data_to_memory(&pool.pool).await.unwrap()
+        tracing::info!("Loading data into memory SQLite database...");
+        let tuning = db::PoolTuning {
+            readers: cli.memory_readers,
+            busy_timeout_ms: cli.memory_busy_timeout_ms,
+            cache_size_kib: cli.memory_cache_kib,
+            mmap_size_bytes: cli.memory_mmap_bytes,
+        };
+        load_data_to_memory(&pool.pool, &tuning, server_metrics.clone())
+            .await
+            .unwrap()
     } else {
         pool
     };
 
     if use_limiter {
-        println!("Using Limiter with rate {} per second", limiter_rate);
+        tracing::info!(rate = limiter_rate, "Using Limiter");
     } else {
-        println!("Not using Limiter");
+        tracing::info!("Not using Limiter");
+    }
+
+    // 统计数据在所有 worker 之间共享一份，而非每个 worker 各自独立统计
+    let persisted_total = db::get_meta_u64(&meta_pool, "lifetime_requests")
+        .await
+        .unwrap_or(0);
+    let req_stats = counter::RequestStats::new(persisted_total);
+
+    // 周期性地将生命周期请求总数刷新到数据库，使其能够跨重启保留
+    {
+        let flush_pool = meta_pool.clone();
+        let flush_stats = req_stats.clone();
+        actix_web::rt::spawn(async move {
+            let mut ticker = actix_web::rt::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                ticker.tick().await;
+                let total = flush_stats.lifetime_total();
+                if let Err(e) = db::set_meta_u64(&flush_pool, "lifetime_requests", total).await {
+                    tracing::error!(error = %e, "Failed to persist lifetime request total");
+                }
+            }
+        });
     }
 
-    println!("Server running at http://{}", bind_addr);
+    tracing::info!(address = %bind_addr, "Server running");
+
+    // 供 `/`、`/{uuid}`、`/batch`、`/update_count` 使用的统一持久化接口，屏蔽具体
+    // 数据库方言；`--store-backend memory` 把整个数据集一次性搬进纯 Rust 的
+    // `InMemoryStore`，此后完全不再经过 SQL 引擎，适合小规模部署
+    let store_data = web::Data::new(match store_backend {
+        StoreBackend::Sql => store::open_store(pool.clone()),
+        StoreBackend::Memory => {
+            tracing::info!("Loading data into the in-memory store backend...");
+            let records = sqlx::query_as::<_, Hitokoto>("SELECT * FROM hitokoto")
+                .fetch_all(&pool.pool)
+                .await
+                .unwrap();
+            store::open_in_memory_store(records)
+        }
+    });
 
     let app_factory = move || {
         let app = App::new().app_data(web::Data::new(pool.clone()));
@@ -212,13 +400,22 @@ async fn main() -> std::io::Result<()> {
                     .unwrap(),
             ))
         };
-        let req_stats = counter::RequestStats::new();
+        let req_stats = req_stats.clone();
+        let server_metrics = server_metrics.clone();
         app.wrap(Compress::default())
             .app_data(web::Data::new(req_stats.clone()))
-            .wrap(counter::RequestCounterMiddleware::new(req_stats.clone()))
+            .app_data(web::Data::new(server_metrics.clone()))
+            .app_data(store_data.clone())
+            .wrap(
+                counter::RequestCounterMiddleware::new(req_stats.clone(), server_metrics.clone())
+                    .with_request_logging(request_log),
+            )
             .wrap(actix_cors::Cors::permissive())
             .route("/stats", web::get().to(counter::get_stats))
+            .route("/metrics", web::get().to(counter::get_metrics))
             .service(get_hitokoto)
+            .service(get_hitokoto_batch)
+            .service(search_hitokoto_route)
             .service(update_count)
             .service(get_hitokoto_by_uuid)
     };
@@ -231,84 +428,111 @@ async fn main() -> std::io::Result<()> {
         .await
 }
 
+// 当 `encode` 未指定时，按 Accept 请求头做内容协商：
+// `text/plain` 返回纯文本，其余情况（包括 `*/*`）返回 JSON。
+fn negotiate_encode(encode: Option<String>, accept: Option<&str>) -> Option<String> {
+    encode.or_else(|| {
+        let accept = accept?;
+        if accept.contains("text/plain") {
+            Some("text".to_string())
+        } else {
+            None
+        }
+    })
+}
+
+// JSONP 回调名只允许字母、数字、下划线、`$`、`.`，防止原样拼进响应体造成
+// 回调注入（任意 JS 甚至 `</script>` 逃逸）
+fn is_valid_jsonp_callback(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.')
+}
+
 fn make_response(
     encode: Option<String>,
+    callback: Option<String>,
+    accept: Option<&str>,
     hitokoto: Result<Option<Hitokoto>, sqlx::Error>,
-) -> impl Responder {
+) -> HttpResponse {
     match hitokoto {
-        Ok(Some(h)) => {
-            if encode == Some("text".to_string()) {
-                Either::Left(
-                    HttpResponse::Ok()
-                        .content_type(ContentType::plaintext())
-                        .body(h.text),
-                )
-            } else {
-                Either::Right(
-                    HttpResponse::Ok()
-                        .content_type(ContentType::json())
-                        .body(h.to_json()),
-                )
+        Ok(Some(h)) => match negotiate_encode(encode, accept).as_deref() {
+            Some("text") => HttpResponse::Ok()
+                .content_type(ContentType::plaintext())
+                .body(h.text),
+            Some("js") => {
+                let callback = callback.unwrap_or_else(|| "callback".to_string());
+                if !is_valid_jsonp_callback(&callback) {
+                    return HttpResponse::BadRequest().body("Invalid callback parameter");
+                }
+                HttpResponse::Ok()
+                    .content_type("application/javascript")
+                    .body(format!("{}({})", callback, h.to_json()))
             }
-        }
-        Ok(None) => Either::Right(HttpResponse::NotFound().body("No hitokoto found")),
-        Err(_) => Either::Right(HttpResponse::InternalServerError().body("Internal Server Error")),
+            _ => HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .body(h.to_json()),
+        },
+        Ok(None) => HttpResponse::NotFound().body("No hitokoto found"),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error"),
     }
 }
 
 #[get("/")]
-async fn get_hitokoto(data: web::Data<DbState>, params: web::Query<QueryParams>) -> impl Responder {
+async fn get_hitokoto(
+    req: HttpRequest,
+    store: web::Data<Box<dyn store::HitokotoStore>>,
+    params: web::Query<QueryParams>,
+) -> HttpResponse {
     let encode = params.encode.clone();
+    let callback = params.callback.clone();
+    let accept = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
     // 如果没有提供任何参数
     if params.c.is_none() && params.min_length.is_none() && params.max_length.is_none() {
-        let hitokoto = rand_hitokoto_without_params(&data).await;
-        return Either::Right(make_response(encode, hitokoto));
+        let hitokoto = store.random().await;
+        return make_response(encode, callback, accept.as_deref(), hitokoto);
     }
 
     // 验证 min_length 和 max_length 的合理性
     if let (Some(min), Some(max)) = (params.min_length, params.max_length) {
         if min < 0 || max < 0 {
-            return Either::Left(
-                HttpResponse::BadRequest().body("The length parameter cannot be negative."),
-            );
+            return HttpResponse::BadRequest().body("The length parameter cannot be negative.");
         }
         if min > max {
-            return Either::Left(
-                HttpResponse::BadRequest()
-                    .body("The min_length cannot be greater than the max_length."),
-            );
+            return HttpResponse::BadRequest()
+                .body("The min_length cannot be greater than the max_length.");
         }
 
         // 检查是否超出数据库中的实际范围
-        let db_min = data.min_length.load(Ordering::Relaxed);
-        let db_max = data.max_length.load(Ordering::Relaxed);
+        let (db_max, db_min) = store.length_stats().await;
 
         if min > db_max || max < db_min {
-            return Either::Left(
-                HttpResponse::BadRequest()
-                    .body("The requested length range exceeds the range of database records"),
-            );
+            return HttpResponse::BadRequest()
+                .body("The requested length range exceeds the range of database records");
         }
     }
 
-    let (query, query_params) = build_query_conditions(&params, data.get_ref());
-    let params_slice: Vec<&str> = query_params.iter().map(|s| s.as_str()).collect();
-    let hitokoto = execute_query_with_params(&data, &query, &params_slice).await;
+    let hitokoto = store.random_filtered(&params).await;
 
-    Either::Right(make_response(encode, hitokoto))
+    make_response(encode, callback, accept.as_deref(), hitokoto)
 }
 
 // 新增路由处理函数修改
 #[get("/{uuid}")]
-async fn get_hitokoto_by_uuid(data: web::Data<DbState>, uuid: web::Path<String>) -> impl Responder {
-    let query = "SELECT * FROM hitokoto WHERE uuid = ? LIMIT 1";
-
-    let hitokoto = execute_query_with_params(&data, query, &[uuid.as_str()])
-        .await
-        .map_err(|e| {
-            eprintln!("Database query error: {}", e);
-            HttpResponse::InternalServerError().body("Internal Server Error")
-        });
+async fn get_hitokoto_by_uuid(
+    store: web::Data<Box<dyn store::HitokotoStore>>,
+    uuid: web::Path<String>,
+) -> impl Responder {
+    let hitokoto = store.by_uuid(uuid.as_str()).await.map_err(|e| {
+        tracing::error!(error = %e, uuid = %uuid.as_str(), "Database query error");
+        HttpResponse::InternalServerError().body("Internal Server Error")
+    });
 
     match hitokoto {
         Ok(Some(h)) => HttpResponse::Ok()
@@ -319,8 +543,71 @@ async fn get_hitokoto_by_uuid(data: web::Data<DbState>, uuid: web::Path<String>)
     }
 }
 
+#[get("/search")]
+async fn search_hitokoto_route(
+    data: web::Data<DbState>,
+    params: web::Query<QueryParams>,
+) -> HttpResponse {
+    match search_hitokoto(&data, &params).await {
+        Ok(results) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(serde_json::to_string(&results).expect("Hitokoto serialization cannot fail")),
+        Err(e) => {
+            tracing::error!(error = %e, "Search query error");
+            HttpResponse::InternalServerError().body("Internal Server Error")
+        }
+    }
+}
+
+#[get("/batch")]
+async fn get_hitokoto_batch(
+    store: web::Data<Box<dyn store::HitokotoStore>>,
+    params: web::Query<BatchQueryParams>,
+) -> HttpResponse {
+    if params.n <= 0 || params.n > BATCH_MAX_N {
+        return HttpResponse::BadRequest().body(format!(
+            "n must be between 1 and {} (got {})",
+            BATCH_MAX_N, params.n
+        ));
+    }
+
+    let query_params = QueryParams {
+        c: params.c.clone(),
+        min_length: params.min_length,
+        max_length: params.max_length,
+        ..Default::default()
+    };
+
+    let opts = db::BatchOptions {
+        limit: params.n as i64,
+        offset: params.offset.unwrap_or(0),
+        reverse: params.reverse.unwrap_or(false),
+        after_id: params.after_id,
+        before_id: params.before_id,
+    };
+
+    let hitokotos = match store.random_batch(&query_params, &opts).await {
+        Ok(h) => h,
+        Err(_) => return HttpResponse::InternalServerError().body("Internal Server Error"),
+    };
+
+    if params.encode == Some("text".to_string()) {
+        let text = hitokotos
+            .iter()
+            .map(|h| h.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(text)
+    } else {
+        let body = serde_json::to_string(&hitokotos).expect("Hitokoto serialization cannot fail");
+        HttpResponse::Ok().content_type(ContentType::json()).body(body)
+    }
+}
+
 #[get("/update_count")]
-async fn update_count(data: web::Data<DbState>) -> impl Responder {
-    data.update().await.unwrap();
+async fn update_count(store: web::Data<Box<dyn store::HitokotoStore>>) -> impl Responder {
+    store.refresh().await.unwrap();
     HttpResponse::Ok().body("Count updated")
 }
@@ -0,0 +1,324 @@
+// store.rs
+//
+// 将持久化逻辑抽象成 `HitokotoStore` trait，使调用方（HTTP handler）不再
+// 直接依赖 `sqlx::AnyPool`/`DbState`，可以换成非 SQL 的实现或在测试中 mock。
+use crate::db::{
+    BatchOptions, DbState, build_query_conditions, execute_query_with_params,
+    rand_hitokoto_batch, rand_hitokoto_without_params,
+};
+use crate::{Hitokoto, QueryParams};
+use async_trait::async_trait;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use sqlx::any::AnyKind;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// 持久化层的统一接口。不同的数据库方言各自实现 `random`/`random_filtered` 的
+/// 随机选取 SQL（MySQL 用 `RAND()`，SQLite/PostgreSQL 用 `RANDOM()`），
+/// 也可以完全不依赖 SQL 引擎（见 [`InMemoryStore`]）。
+#[async_trait]
+pub trait HitokotoStore: Send + Sync {
+    /// 不带过滤条件的随机一条
+    async fn random(&self) -> Result<Option<Hitokoto>, sqlx::Error>;
+    /// 按 `type`/`length` 过滤后的随机一条
+    async fn random_filtered(&self, params: &QueryParams) -> Result<Option<Hitokoto>, sqlx::Error>;
+    async fn by_uuid(&self, uuid: &str) -> Result<Option<Hitokoto>, sqlx::Error>;
+    /// 按 `BatchOptions` 批量获取，详见 `db::rand_hitokoto_batch`
+    async fn random_batch(
+        &self,
+        filter: &QueryParams,
+        opts: &BatchOptions,
+    ) -> Result<Vec<Hitokoto>, sqlx::Error>;
+    async fn count(&self) -> i32;
+    /// 返回 `(max_length, min_length)`
+    async fn length_stats(&self) -> (i32, i32);
+    /// 重新从底层数据源加载统计信息（总数、长度范围、uuid 列表等）
+    async fn refresh(&self) -> Result<(), sqlx::Error>;
+}
+
+async fn random_with_filter(
+    state: &DbState,
+    params: &QueryParams,
+) -> Result<Option<Hitokoto>, sqlx::Error> {
+    if params.c.is_none() && params.min_length.is_none() && params.max_length.is_none() {
+        return rand_hitokoto_without_params(state).await;
+    }
+
+    let (query, query_params) = build_query_conditions(params, state);
+    let params_slice: Vec<&str> = query_params.iter().map(|s| s.as_str()).collect();
+    execute_query_with_params(state, &query, &params_slice).await
+}
+
+macro_rules! store_impl {
+    ($name:ident) => {
+        pub struct $name(pub DbState);
+
+        impl $name {
+            fn state(&self) -> &DbState {
+                &self.0
+            }
+        }
+
+        #[async_trait]
+        impl HitokotoStore for $name {
+            async fn random(&self) -> Result<Option<Hitokoto>, sqlx::Error> {
+                rand_hitokoto_without_params(self.state()).await
+            }
+
+            async fn random_filtered(
+                &self,
+                params: &QueryParams,
+            ) -> Result<Option<Hitokoto>, sqlx::Error> {
+                random_with_filter(self.state(), params).await
+            }
+
+            async fn by_uuid(&self, uuid: &str) -> Result<Option<Hitokoto>, sqlx::Error> {
+                execute_query_with_params(
+                    self.state(),
+                    "SELECT * FROM hitokoto WHERE uuid = ? LIMIT 1",
+                    &[uuid],
+                )
+                .await
+            }
+
+            async fn random_batch(
+                &self,
+                filter: &QueryParams,
+                opts: &BatchOptions,
+            ) -> Result<Vec<Hitokoto>, sqlx::Error> {
+                rand_hitokoto_batch(self.state(), filter, opts).await
+            }
+
+            async fn count(&self) -> i32 {
+                self.state().count.load(Ordering::Relaxed)
+            }
+
+            async fn length_stats(&self) -> (i32, i32) {
+                (
+                    self.state().max_length.load(Ordering::Relaxed),
+                    self.state().min_length.load(Ordering::Relaxed),
+                )
+            }
+
+            async fn refresh(&self) -> Result<(), sqlx::Error> {
+                self.state().update().await
+            }
+        }
+    };
+}
+
+store_impl!(SqliteStore);
+store_impl!(MySqlStore);
+store_impl!(PostgresStore);
+
+/// 根据连接池实际的数据库方言，选择对应的 `HitokotoStore` 实现
+pub fn open_store(state: DbState) -> Box<dyn HitokotoStore> {
+    match state.pool.any_kind() {
+        AnyKind::MySql => Box::new(MySqlStore(state)),
+        AnyKind::Postgres => Box::new(PostgresStore(state)),
+        _ => Box::new(SqliteStore(state)),
+    }
+}
+
+/// 纯内存实现：数据直接保存在 `Vec<Hitokoto>` 里，uuid 查找用 `HashMap` 做索引，
+/// 随机选取也完全在 Rust 里完成，不依赖任何 SQL 引擎。适合小规模部署或单测 mock，
+/// 通过 `--store-backend memory` 选用（见 `main.rs`）。
+pub struct InMemoryStore {
+    records: Vec<Hitokoto>,
+    index_by_uuid: HashMap<String, usize>,
+    max_length: i32,
+    min_length: i32,
+}
+
+impl InMemoryStore {
+    pub fn new(records: Vec<Hitokoto>) -> Self {
+        let index_by_uuid = records
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.uuid.clone(), i))
+            .collect();
+        let max_length = records.iter().map(|h| h.length).max().unwrap_or(0);
+        let min_length = records.iter().map(|h| h.length).min().unwrap_or(0);
+
+        Self {
+            records,
+            index_by_uuid,
+            max_length,
+            min_length,
+        }
+    }
+
+    fn matches(h: &Hitokoto, params: &QueryParams) -> bool {
+        if let Some(categories) = &params.c {
+            if !categories.split(',').any(|c| c == h.r#type) {
+                return false;
+            }
+        }
+        if let Some(min) = params.min_length {
+            if h.length < min {
+                return false;
+            }
+        }
+        if let Some(max) = params.max_length {
+            if h.length > max {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl HitokotoStore for InMemoryStore {
+    async fn random(&self) -> Result<Option<Hitokoto>, sqlx::Error> {
+        if self.records.is_empty() {
+            return Ok(None);
+        }
+        let idx = rand::rng().random_range(0..self.records.len());
+        Ok(Some(self.records[idx].clone()))
+    }
+
+    async fn random_filtered(&self, params: &QueryParams) -> Result<Option<Hitokoto>, sqlx::Error> {
+        let matching: Vec<&Hitokoto> = self
+            .records
+            .iter()
+            .filter(|h| Self::matches(h, params))
+            .collect();
+        if matching.is_empty() {
+            return Ok(None);
+        }
+        let idx = rand::rng().random_range(0..matching.len());
+        Ok(Some(matching[idx].clone()))
+    }
+
+    async fn by_uuid(&self, uuid: &str) -> Result<Option<Hitokoto>, sqlx::Error> {
+        Ok(self.index_by_uuid.get(uuid).map(|&i| self.records[i].clone()))
+    }
+
+    async fn random_batch(
+        &self,
+        filter: &QueryParams,
+        opts: &BatchOptions,
+    ) -> Result<Vec<Hitokoto>, sqlx::Error> {
+        let mut matching: Vec<&Hitokoto> =
+            self.records.iter().filter(|h| Self::matches(h, filter)).collect();
+
+        // 与 `db::rand_hitokoto_batch` 保持一致：只有显式要求确定性排序
+        // （reverse/after_id/before_id）时才放弃随机抽样
+        let wants_deterministic_order =
+            opts.reverse || opts.after_id.is_some() || opts.before_id.is_some();
+
+        if wants_deterministic_order {
+            matching.sort_by_key(|h| h.id);
+            if let Some(after) = opts.after_id {
+                matching.retain(|h| h.id > after);
+            }
+            if let Some(before) = opts.before_id {
+                matching.retain(|h| h.id < before);
+            }
+            if opts.reverse {
+                matching.reverse();
+            }
+        } else {
+            matching.shuffle(&mut rand::rng());
+        }
+
+        let start = (opts.offset.max(0) as usize).min(matching.len());
+        let end = start.saturating_add(opts.limit.max(0) as usize).min(matching.len());
+        Ok(matching[start..end].iter().map(|h| (*h).clone()).collect())
+    }
+
+    async fn count(&self) -> i32 {
+        self.records.len() as i32
+    }
+
+    async fn length_stats(&self) -> (i32, i32) {
+        (self.max_length, self.min_length)
+    }
+
+    async fn refresh(&self) -> Result<(), sqlx::Error> {
+        // 纯内存实现没有可重新拉取的外部数据源，保持当前快照即可
+        Ok(())
+    }
+}
+
+/// 包装一份内存中的记录集合为 `HitokotoStore`
+pub fn open_in_memory_store(records: Vec<Hitokoto>) -> Box<dyn HitokotoStore> {
+    Box::new(InMemoryStore::new(records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(id: i32, uuid: &str, r#type: &str, length: i32) -> Hitokoto {
+        Hitokoto {
+            id,
+            uuid: uuid.to_string(),
+            text: format!("text-{id}"),
+            r#type: r#type.to_string(),
+            from_source: "test".to_string(),
+            from_who: None,
+            length,
+        }
+    }
+
+    fn store() -> InMemoryStore {
+        InMemoryStore::new(vec![
+            sample(1, "a", "a", 5),
+            sample(2, "b", "b", 10),
+            sample(3, "c", "a", 15),
+        ])
+    }
+
+    #[actix_web::test]
+    async fn random_returns_one_of_the_records() {
+        let store = store();
+        let h = store.random().await.unwrap().unwrap();
+        assert!(["a", "b", "c"].contains(&h.uuid.as_str()));
+    }
+
+    #[actix_web::test]
+    async fn by_uuid_finds_the_matching_record() {
+        let store = store();
+        let h = store.by_uuid("b").await.unwrap().unwrap();
+        assert_eq!(h.id, 2);
+        assert!(store.by_uuid("missing").await.unwrap().is_none());
+    }
+
+    #[actix_web::test]
+    async fn random_filtered_respects_type_and_length() {
+        let store = store();
+        let params = QueryParams {
+            c: Some("a".to_string()),
+            min_length: Some(10),
+            ..Default::default()
+        };
+        let h = store.random_filtered(&params).await.unwrap().unwrap();
+        assert_eq!(h.r#type, "a");
+        assert!(h.length >= 10);
+    }
+
+    #[actix_web::test]
+    async fn random_batch_deterministic_order_applies_after_id_and_reverse() {
+        let store = store();
+        let opts = BatchOptions {
+            limit: 10,
+            offset: 0,
+            reverse: true,
+            after_id: Some(1),
+            before_id: None,
+        };
+        let results = store.random_batch(&QueryParams::default(), &opts).await.unwrap();
+        let ids: Vec<i32> = results.iter().map(|h| h.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+    }
+
+    #[actix_web::test]
+    async fn count_and_length_stats_match_loaded_records() {
+        let store = store();
+        assert_eq!(store.count().await, 3);
+        assert_eq!(store.length_stats().await, (15, 5));
+    }
+}